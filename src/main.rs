@@ -1,15 +1,15 @@
-use actix_web::{get, App, HttpServer, Responder};
+use actix_web::{get, web, App, HttpServer, Responder};
 use actix_web::middleware::Logger;
 
 use libzetta::zpool::{ZpoolOpen3, ZpoolEngine, Vdev, Health, vdev::ErrorStatistics, Reason};
 
-use prometheus::{Encoder, IntCounter, Registry};
+use prometheus::{Encoder, Gauge, IntCounter, IntGauge, Registry};
 
 use clap::Parser;
 
 use std::{collections::HashMap, string::FromUtf8Error, process::Command};
 
-use log::{error, debug};
+use log::{error, warn, debug};
 
 fn encode_metrics(reg: &Registry) -> Result<String, FromUtf8Error> {
     let mut buffer: Vec<u8> = Vec::new();
@@ -28,6 +28,22 @@ fn register_intcounter(reg: &Registry, name: &str, help: &str, val: u64) -> prom
     Ok(())
 }
 
+fn register_intgauge(reg: &Registry, name: &str, help: &str, val: i64) -> prometheus::Result<()> {
+    let gauge = IntGauge::new(name, help)?;
+    gauge.set(val);
+    reg.register(Box::new(gauge))?;
+
+    Ok(())
+}
+
+fn register_gauge(reg: &Registry, name: &str, help: &str, val: f64) -> prometheus::Result<()> {
+    let gauge = Gauge::new(name, help)?;
+    gauge.set(val);
+    reg.register(Box::new(gauge))?;
+
+    Ok(())
+}
+
 fn register_health(labels: HashMap<String, String>, health: Health) -> prometheus::Result<Vec<Registry>> {
     let mut labels = labels;
     labels.insert(String::from("field_type"), String::from("enum"));
@@ -38,7 +54,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Online => 1,
         _ => 0,
     };
-    register_intcounter(&online_reg, "health", "The health of the device. This is an enum.", online_val)?;
+    register_intgauge(&online_reg, "health", "The health of the device. This is an enum.", online_val)?;
 
     labels.insert(String::from("state"), String::from("degraded"));
     let degraded_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -46,7 +62,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Degraded => 1,
         _ => 0,
     };
-    register_intcounter(&degraded_reg, "health", "The health of the device. This is an enum.", degraded_val)?;
+    register_intgauge(&degraded_reg, "health", "The health of the device. This is an enum.", degraded_val)?;
 
     labels.insert(String::from("state"), String::from("faulted"));
     let faulted_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -54,7 +70,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Faulted => 1,
         _ => 0,
     };
-    register_intcounter(&faulted_reg, "health", "The health of the device. This is an enum.", faulted_val)?;
+    register_intgauge(&faulted_reg, "health", "The health of the device. This is an enum.", faulted_val)?;
 
     labels.insert(String::from("state"), String::from("offline"));
     let offline_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -62,7 +78,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Offline => 1,
         _ => 0,
     };
-    register_intcounter(&offline_reg, "health", "The health of the device. This is an enum.", offline_val)?;
+    register_intgauge(&offline_reg, "health", "The health of the device. This is an enum.", offline_val)?;
 
     labels.insert(String::from("state"), String::from("available"));
     let available_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -70,7 +86,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Available => 1,
         _ => 0,
     };
-    register_intcounter(&available_reg, "health", "The health of the device. This is an enum.", available_val)?;
+    register_intgauge(&available_reg, "health", "The health of the device. This is an enum.", available_val)?;
 
     labels.insert(String::from("state"), String::from("unavailable"));
     let unavailable_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -78,7 +94,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Unavailable => 1,
         _ => 0,
     };
-    register_intcounter(&unavailable_reg, "health", "The health of the device. This is an enum.", unavailable_val)?;
+    register_intgauge(&unavailable_reg, "health", "The health of the device. This is an enum.", unavailable_val)?;
 
     labels.insert(String::from("state"), String::from("removed"));
     let removed_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
@@ -86,7 +102,7 @@ fn register_health(labels: HashMap<String, String>, health: Health) -> prometheu
         Health::Removed => 1,
         _ => 0,
     };
-    register_intcounter(&removed_reg, "health", "The health of the device. This is an enum.", removed_val)?;
+    register_intgauge(&removed_reg, "health", "The health of the device. This is an enum.", removed_val)?;
 
     Ok(vec![online_reg, degraded_reg, faulted_reg, offline_reg, available_reg, unavailable_reg, removed_reg])
 }
@@ -114,14 +130,398 @@ fn register_vdev_stats(vdev: &Vdev, vdev_device: &Device, vdev_name: String, sta
     Ok(vdev_reg)
 }
 
+/// Runs `zpool iostat -Hpvy <pool> 1 1` and parses its output, or returns
+/// `Err` describing what went wrong instead of panicking, so a transient
+/// failure on one pool doesn't take down the whole scrape.
+///
+/// The `-y` flag makes this block for the 1 second interval so the single
+/// sample it prints reflects the rate over that second, rather than the
+/// average since boot.
+fn run_zpool_iostat(pool_name: &str) -> Result<Vec<Device>, String> {
+    run_zpool_iostat_with_args(pool_name, &["iostat", "-Hpvy", pool_name, "1", "1"])
+}
+
+/// Runs `zpool iostat -Hpv <pool>` and parses its output the same way as
+/// [`run_zpool_iostat`], but without `-y` or an interval/count, so it prints
+/// a single since-boot report immediately instead of blocking for a second.
+/// Used to discover vdev/disk topology in `IostatSource::Kstat` mode, where
+/// the pool's own IO rate already comes from kstat and paying the `-y`
+/// sampling sleep just for topology would defeat the point of that flag.
+fn run_zpool_iostat_instant(pool_name: &str) -> Result<Vec<Device>, String> {
+    run_zpool_iostat_with_args(pool_name, &["iostat", "-Hpv", pool_name])
+}
+
+fn run_zpool_iostat_with_args(pool_name: &str, args: &[&str]) -> Result<Vec<Device>, String> {
+    let output = Command::new("zpool")
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to execute `zpool iostat`: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`zpool iostat {}` exited with {}, stderr: {}",
+            pool_name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr),
+        ));
+    }
+
+    let output = String::from_utf8(output.stdout)
+        .map_err(|e| format!("`zpool iostat {}` output was not valid utf8: {}", pool_name, e))?;
+
+    Ok(Device::parse_from_stdout(output))
+}
+
+/// Runs `zpool status <pool>` and returns its raw stdout, or `None` (with a
+/// logged error) if the command fails or its output isn't valid UTF-8.
+fn run_zpool_status(pool_name: &str) -> Option<String> {
+    let output = match Command::new("zpool").args(["status", pool_name]).output() {
+        Ok(output) => output,
+        Err(e) => {
+            error!("Failed to execute `zpool status {}`: {}", pool_name, e);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        error!("`zpool status {}` exited with {}", pool_name, output.status);
+        return None;
+    }
+
+    match String::from_utf8(output.stdout) {
+        Ok(stdout) => Some(stdout),
+        Err(e) => {
+            error!("Failed to convert `zpool status {}` output to utf8: {}", pool_name, e);
+            None
+        }
+    }
+}
+
+/// A single vdev or leaf disk from the `NAME STATE READ WRITE CKSUM` tree in
+/// `zpool status` output, with its children nested according to indentation.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct VdevStatus {
+    name: String,
+    level: usize,
+    state: Option<String>,
+    read_errors: u64,
+    write_errors: u64,
+    checksum_errors: u64,
+    children: Vec<VdevStatus>,
+}
+
+impl VdevStatus {
+    /// Assigns `level` from actual tree depth (root = 0), recursively.
+    fn assign_levels(&mut self, level: usize) {
+        self.level = level;
+        for child in self.children.iter_mut() {
+            child.assign_levels(level + 1);
+        }
+    }
+
+    /// Parses the vdev tree body (the lines between the `NAME ... CKSUM`
+    /// header and the next blank line) into a single root node.
+    ///
+    /// Indentation is used as a stack depth, with one wrinkle: `zpool
+    /// status` prints the `logs`/`cache`/`spares` section headers at the
+    /// *same* indentation as the pool name itself, even though they're
+    /// semantically children of it, with their own disks indented only one
+    /// level (the same width as a top-level vdev). So any line at
+    /// or shallower than the root's indentation, other than the root line
+    /// itself, is treated as starting a new child of the root rather than
+    /// being allowed to pop the root off the stack.
+    fn parse_tree(config_lines: &[&str]) -> Option<VdevStatus> {
+        let mut raw: Vec<(usize, VdevStatus)> = Vec::new();
+
+        for line in config_lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.is_empty() || cols[0].eq_ignore_ascii_case("NAME") {
+                continue;
+            }
+
+            let indent = line.len() - line.trim_start().len();
+            raw.push((indent, VdevStatus {
+                name: cols[0].to_string(),
+                level: 0,
+                state: cols.get(1).map(|s| s.to_string()),
+                read_errors: cols.get(2).and_then(|s| s.parse().ok()).unwrap_or(0),
+                write_errors: cols.get(3).and_then(|s| s.parse().ok()).unwrap_or(0),
+                checksum_errors: cols.get(4).and_then(|s| s.parse().ok()).unwrap_or(0),
+                children: Vec::new(),
+            }));
+        }
+
+        if raw.is_empty() {
+            return None;
+        }
+
+        let root_indent = raw[0].0;
+        let mut stack: Vec<(usize, VdevStatus)> = Vec::new();
+
+        for (indent, node) in raw {
+            if stack.is_empty() {
+                stack.push((indent, node));
+                continue;
+            }
+
+            let indent = if indent <= root_indent { root_indent + 1 } else { indent };
+
+            while stack.len() > 1 && stack.last().unwrap().0 >= indent {
+                let (_, child) = stack.pop().unwrap();
+                stack.last_mut().unwrap().1.children.push(child);
+            }
+
+            stack.push((indent, node));
+        }
+
+        while stack.len() > 1 {
+            let (_, child) = stack.pop().unwrap();
+            stack.last_mut().unwrap().1.children.push(child);
+        }
+
+        stack.pop().map(|(_, mut root)| {
+            root.assign_levels(0);
+            root
+        })
+    }
+}
+
+/// Scrub/resilver progress parsed from the `scan:` field of `zpool status`.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct ScanStatus {
+    scrub_in_progress: bool,
+    resilver_in_progress: bool,
+    progress_ratio: Option<f64>,
+    bytes_scanned: Option<u64>,
+    bytes_total: Option<u64>,
+    bytes_repaired: Option<u64>,
+    errors_repaired: Option<u64>,
+    time_to_go_seconds: Option<u64>,
+}
+
+/// The top-level fields `zpool status` prints alongside `scan:`, used to
+/// detect where a wrapped, multi-line `scan:` value ends.
+const STATUS_TOP_LEVEL_FIELDS: [&str; 7] = ["pool:", "state:", "status:", "action:", "see:", "config:", "errors:"];
+
+/// Parses a human-readable ZFS size like `512G` or `0B` into bytes.
+fn parse_zfs_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split_at = s.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (num_part, unit) = s.split_at(split_at);
+    let num: f64 = num_part.parse().ok()?;
+
+    let multiplier = match unit.chars().next()? {
+        'B' => 1.0_f64,
+        'K' => 1024.0_f64,
+        'M' => 1024.0_f64.powi(2),
+        'G' => 1024.0_f64.powi(3),
+        'T' => 1024.0_f64.powi(4),
+        'P' => 1024.0_f64.powi(5),
+        _ => return None,
+    };
+
+    Some((num * multiplier) as u64)
+}
+
+/// Returns the whitespace/comma-delimited token immediately before `marker`.
+fn token_before<'a>(text: &'a str, marker: &str) -> Option<&'a str> {
+    let idx = text.find(marker)?;
+    let before = text[..idx].trim_end();
+    let start = before.rfind(|c: char| c.is_whitespace() || c == ',').map(|i| i + 1).unwrap_or(0);
+    Some(&before[start..])
+}
+
+/// Parses a `"<N> days HH:MM:SS to go"` suffix into a number of seconds.
+fn parse_time_to_go(text: &str) -> Option<u64> {
+    let idx = text.find("to go")?;
+    let before = text[..idx].trim_end();
+
+    let mut parts = before.rsplitn(3, char::is_whitespace);
+    let hms = parts.next()?;
+    let days_word = parts.next()?;
+    if days_word != "days" && days_word != "day" {
+        return None;
+    }
+    let days: u64 = parts.next()?.rsplit(|c: char| c.is_whitespace() || c == ',').next()?.parse().ok()?;
+
+    let hms_parts: Vec<&str> = hms.split(':').collect();
+    if hms_parts.len() != 3 {
+        return None;
+    }
+    let hours: u64 = hms_parts[0].parse().ok()?;
+    let minutes: u64 = hms_parts[1].parse().ok()?;
+    let seconds: u64 = hms_parts[2].parse().ok()?;
+
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Parses the (possibly multi-line) text of a `scan:` field leniently: any
+/// piece that isn't recognized is simply left as `None` rather than failing
+/// the whole parse, since a healthy pool's `scan: none requested` and a
+/// resilvering pool's multi-line progress report share no common shape.
+fn parse_scan_status(scan_text: &str) -> ScanStatus {
+    let mut status = ScanStatus::default();
+    let lowered = scan_text.to_lowercase();
+
+    status.scrub_in_progress = lowered.contains("scrub in progress");
+    status.resilver_in_progress = lowered.contains("resilver in progress") || lowered.contains("resilvering");
+
+    status.progress_ratio = token_before(scan_text, "% done")
+        .and_then(|t| t.parse::<f64>().ok())
+        .map(|pct| pct / 100.0);
+
+    status.bytes_scanned = token_before(scan_text, " scanned").and_then(parse_zfs_size);
+    status.bytes_total = token_before(scan_text, " total").and_then(parse_zfs_size);
+
+    // An in-progress scrub/resilver reports data fixed so far as a
+    // "<size> repaired" byte count, not an error tally; only a *completed*
+    // scan's summary line gives an actual "with <N> errors" count. Surface
+    // both rather than letting the in-progress case silently read as zero.
+    status.bytes_repaired = token_before(scan_text, " repaired").and_then(parse_zfs_size);
+
+    status.errors_repaired = token_before(scan_text, " errors")
+        .or_else(|| token_before(scan_text, " error"))
+        .and_then(|t| t.parse::<u64>().ok());
+
+    status.time_to_go_seconds = parse_time_to_go(&lowered);
+
+    status
+}
+
+/// A pool's parsed `zpool status` output: its scrub/resilver progress and
+/// its vdev state tree.
+#[derive(Debug, Clone, PartialEq)]
+struct PoolStatus {
+    vdev_tree: Option<VdevStatus>,
+    scan: ScanStatus,
+}
+
+impl PoolStatus {
+    fn parse(stdout: &str) -> PoolStatus {
+        let lines: Vec<&str> = stdout.lines().collect();
+
+        let mut scan_text = String::new();
+        if let Some(scan_start) = lines.iter().position(|l| l.trim_start().starts_with("scan:")) {
+            scan_text.push_str(lines[scan_start].trim());
+
+            // The `scan:` value can wrap onto further indented lines, up
+            // until the next top-level field or a blank line.
+            for line in &lines[scan_start + 1..] {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || STATUS_TOP_LEVEL_FIELDS.iter().any(|f| trimmed.starts_with(f)) {
+                    break;
+                }
+                scan_text.push(' ');
+                scan_text.push_str(trimmed);
+            }
+        }
+
+        let vdev_tree = lines.iter().position(|l| {
+            let cols: Vec<&str> = l.split_whitespace().collect();
+            cols.first() == Some(&"NAME") && cols.contains(&"STATE")
+        }).and_then(|header_idx| {
+            let body: Vec<&str> = lines[header_idx + 1..].iter()
+                .take_while(|l| !l.trim().is_empty())
+                .cloned()
+                .collect();
+            VdevStatus::parse_tree(&body)
+        });
+
+        PoolStatus {
+            vdev_tree,
+            scan: parse_scan_status(&scan_text),
+        }
+    }
+}
+
+/// Recursively registers per-vdev state and IO-error metrics for a vdev
+/// status node and all its children, labeled by name and nesting level so
+/// raidz/mirror grouping vdevs are represented rather than inferred.
+fn register_vdev_status(base_labels: &HashMap<String, String>, vdev: &VdevStatus) -> prometheus::Result<Vec<Registry>> {
+    let mut registries = Vec::new();
+
+    let mut labels = base_labels.clone();
+    labels.insert(String::from("device_name"), vdev.name.clone());
+    labels.insert(String::from("vdev_level"), vdev.level.to_string());
+
+    // The root node is the pool itself (already labeled `device_type="pool"`
+    // by the caller); everything below it is either a vdev (it groups child
+    // disks, e.g. a mirror/raidz, or a logs/cache/spares section) or a leaf
+    // disk. Without this, every node in the tree would inherit the pool's
+    // `device_type`, making vdev- and disk-level series indistinguishable
+    // from pool-level ones under the label the rest of the file uses.
+    if vdev.level > 0 {
+        let device_type = if vdev.children.is_empty() { "disk" } else { "vdev" };
+        labels.insert(String::from("device_type"), String::from(device_type));
+    }
+
+    let reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone()))?;
+    register_intcounter(&reg, "vdev_read_errors", "Read errors reported by `zpool status` for this vdev", vdev.read_errors)?;
+    register_intcounter(&reg, "vdev_write_errors", "Write errors reported by `zpool status` for this vdev", vdev.write_errors)?;
+    register_intcounter(&reg, "vdev_checksum_errors", "Checksum errors reported by `zpool status` for this vdev", vdev.checksum_errors)?;
+    registries.push(reg);
+
+    if let Some(state) = &vdev.state {
+        let mut state_labels = labels;
+        state_labels.insert(String::from("state"), state.to_lowercase());
+
+        let state_reg = Registry::new_custom(Some("zfs".to_string()), Some(state_labels))?;
+        register_intgauge(&state_reg, "vdev_state", "The state reported by `zpool status` for this vdev. This is an enum.", 1)?;
+        registries.push(state_reg);
+    }
+
+    for child in &vdev.children {
+        registries.extend(register_vdev_status(base_labels, child)?);
+    }
+
+    Ok(registries)
+}
+
+/// Cheaply checks whether `zpool` is usable on this host before attempting a
+/// full scrape, so a missing/broken ZFS installation produces an empty (but
+/// 200 OK) response with a logged warning instead of taking down the worker.
+fn should_collect() -> bool {
+    match Command::new("zpool").arg("list").output() {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            warn!("`zpool list` exited with {}, skipping this scrape", output.status);
+            false
+        }
+        Err(e) => {
+            warn!("Failed to execute `zpool`, skipping this scrape: {}", e);
+            false
+        }
+    }
+}
+
 #[get("/metrics")]
-async fn metrics_endpoint() -> impl Responder {
+async fn metrics_endpoint(iostat_source: web::Data<IostatSource>) -> impl Responder {
+    let scrape_start = std::time::Instant::now();
+
+    if !should_collect() {
+        return String::new();
+    }
+
     let zpool = ZpoolOpen3::default();
-    let all_pools = zpool.all().unwrap(); // TODO: Dont unwrap
+    let all_pools = match zpool.all() {
+        Ok(pools) => pools,
+        Err(e) => {
+            error!("Failed to list zpools: {}", e);
+            return String::new();
+        }
+    };
 
     let mut registries = Vec::new();
 
     for pool in all_pools.iter() {
+        let pool_name = pool.name().clone();
+        let pool_result: Result<Vec<Registry>, String> = (|| {
+        let mut registries = Vec::new();
+
         // Print some stuff that can be used for later features.
         // My pool is in a healthy state currently, so I can't actually work on these
         // to see what they output.
@@ -147,64 +547,125 @@ async fn metrics_endpoint() -> impl Responder {
         labels.insert(String::from("device_name"), pool.name().clone());
 
         // Create a registry for general pool metrics
-        let pool_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone())).unwrap();
+        let pool_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone())).map_err(|e| e.to_string())?;
 
-        register_intcounter(&pool_reg, "vdev_count", "Count of vdevs in this pool", pool.vdevs().len() as u64).unwrap();
-        register_intcounter(&pool_reg, "spare_count", "The amount of spare drives", pool.spares().len() as u64).unwrap();
+        register_intcounter(&pool_reg, "vdev_count", "Count of vdevs in this pool", pool.vdevs().len() as u64).map_err(|e| e.to_string())?;
+        register_intcounter(&pool_reg, "spare_count", "The amount of spare drives", pool.spares().len() as u64).map_err(|e| e.to_string())?;
 
         // Calculate the total drive count and register it as a metric.
-        let total_disk_count = IntCounter::new("disk_count", "Total count of drives in this pool or vdev").unwrap();
+        let total_disk_count = IntCounter::new("disk_count", "Total count of drives in this pool or vdev").map_err(|e| e.to_string())?;
         for vdev in pool.vdevs().iter() {
             total_disk_count.inc_by(vdev.disks().len() as u64);
         }
-        pool_reg.register(Box::new(total_disk_count)).unwrap();
+        pool_reg.register(Box::new(total_disk_count)).map_err(|e| e.to_string())?;
 
         // Register pool health
-        registries.extend(register_health(labels.clone(), pool.health().clone()).unwrap());
-        register_error_stats(&pool_reg, pool.error_statistics().clone()).unwrap();
-
-        // Run the zpool iostat command to get io stat information of all the pool, its vdevs and disks.
-        let mut cmd = Command::new("zpool");
-        cmd.args(["iostat", "-Hpvy", pool.name().as_str(), "1", "1"]);
-        let output = cmd.output();
-        let output = output.expect(&format!("Failure to execute `zpool iostat`"));
-
-        // Check if the `zpool iostat` command executed successfully.
-        if !output.status.success() {
-            error!("Failed to execute `zpool iostat`!");
-            error!("Full command: `{:?} {}`", cmd.get_program(), cmd.get_args()
-                .into_iter()
-                .map(|arg| arg.to_str().unwrap().to_string())
-                .collect::<Vec<String>>()
-                .join(" "));
-
-            error!("stdout:\n{:?}", output.stdout);
-            error!("stderr:\n{:?}", output.stderr);
-            error!("exit code: {}", output.status);
-            panic!("Failure to execute zpool iostat!");
+        registries.extend(register_health(labels.clone(), pool.health().clone()).map_err(|e| e.to_string())?);
+        register_error_stats(&pool_reg, pool.error_statistics().clone()).map_err(|e| e.to_string())?;
+
+        // Get io stat information of the pool, its vdevs and disks. Vdev/disk level
+        // stats still require `zpool iostat`, but the pool's own IO numbers can be
+        // read straight from the kernel's kstats instead, skipping the subprocess
+        // and its 1 second sampling sleep.
+        let devices = match *iostat_source.get_ref() {
+            IostatSource::Kstat => {
+                match KstatIo::read_for_pool(pool.name().as_str()) {
+                    Ok(kstat) => {
+                        kstat.collect_metrics(&pool_reg).map_err(|e| e.to_string())?;
+                    }
+                    Err(e) => {
+                        error!("Failed to read kstat io for pool '{}': {}", pool.name(), e);
+                    }
+                }
+
+                // Still need `zpool iostat` for vdev/disk topology and their
+                // own IO numbers, but use the instant variant so this mode
+                // doesn't pay the `-y` sampling sleep for data it's already
+                // gotten from kstat above.
+                run_zpool_iostat_instant(pool.name().as_str())?
+            }
+            IostatSource::Command => {
+                let devices = run_zpool_iostat(pool.name().as_str())?;
+
+                // Get the pool from the devices and collect the io stats
+                if let Some(pool_dev) = devices.iter().find(|dev| dev.name == pool.name().clone()) {
+                    pool_dev.io_stats.collect_metrics(&pool_reg).map_err(|e| e.to_string())?;
+                }
+
+                devices
+            }
+        };
+
+        // Get the raw size of the pool.
+        {
+            let output = Command::new("zpool")
+                .args(["list", "-Hp", pool.name().as_str()])
+                .output()
+                .map_err(|e| format!("failed to execute `zpool list {}`: {}", pool.name(), e))?;
+
+            if !output.status.success() {
+                return Err(format!("`zpool list {}` exited with {}", pool.name(), output.status));
+            }
+
+            let output = String::from_utf8(output.stdout)
+                .map_err(|e| format!("`zpool list {}` output was not valid utf8: {}", pool.name(), e))?;
+
+            let stats = PoolListStats::parse(output.trim_end());
+
+            if let Some(size) = stats.size {
+                register_intgauge(&pool_reg, "raw_size", "The raw size of this device (this is not the usable space)", size as i64).map_err(|e| e.to_string())?;
+            }
+            if let Some(alloc) = stats.alloc {
+                register_intgauge(&pool_reg, "alloc", "The allocated bytes of this pool", alloc as i64).map_err(|e| e.to_string())?;
+            }
+            if let Some(free) = stats.free {
+                register_intgauge(&pool_reg, "free", "The free bytes of this pool", free as i64).map_err(|e| e.to_string())?;
+            }
+            if let Some(frag) = stats.fragmentation_percent {
+                register_gauge(&pool_reg, "fragmentation_percent", "The amount of fragmentation in the pool, as a percentage", frag).map_err(|e| e.to_string())?;
+            }
+            if let Some(cap) = stats.capacity_percent {
+                register_gauge(&pool_reg, "capacity_percent", "The percentage of the pool's raw size that is allocated", cap).map_err(|e| e.to_string())?;
+            }
+            if let Some(dedup) = stats.dedup_ratio {
+                register_gauge(&pool_reg, "dedup_ratio", "The deduplication ratio of the pool", dedup).map_err(|e| e.to_string())?;
+            }
         }
-        let output = String::from_utf8(output.stdout)
-            .expect(&format!("Failure to convert output of `zpool iostat` to utf8."));
-
-        let devices = Device::parse_from_stdout(output);
-
-        // Get the pool from the devices and collect the io stats
-        if let Some(pool_dev) = devices.iter().find(|dev| dev.name == pool.name().clone()) {
-            pool_dev.io_stats.collect_metrics(&pool_reg).unwrap();
-
-            // Get the raw size of the pool.
-            let output = String::from_utf8(
-                Command::new("zpool")
-                    .args(["list", "-Hp", pool.name().as_str()])
-                    .output()
-                    .expect(&format!("Failure to execute `zpool iostat {} -v 1 2`", pool.name()))
-                .stdout).expect(&format!("Failure to convert output of `zpool iostat {} -v 1 2` to utf8.", pool.name()));
-
-            // Extract the size from the output
-            let cols: Vec<&str> = output.split("\t").collect();
-            if cols.len() == 11 {
-                let size: u64 = cols[1].parse().unwrap();
-                register_intcounter(&pool_reg, "raw_size", "The raw size of this device (this is not the usable space)", size).unwrap();
+
+        // Parse `zpool status` for scrub/resilver progress and the real vdev
+        // state tree, instead of guessing vdev boundaries from the iostat output.
+        if let Some(status_output) = run_zpool_status(pool.name().as_str()) {
+            let status = PoolStatus::parse(&status_output);
+            let scan = &status.scan;
+
+            register_intgauge(&pool_reg, "scrub_in_progress", "Whether a scrub is currently running on this pool", scan.scrub_in_progress as i64).map_err(|e| e.to_string())?;
+            register_intgauge(&pool_reg, "resilver_in_progress", "Whether a resilver is currently running on this pool", scan.resilver_in_progress as i64).map_err(|e| e.to_string())?;
+            // Only a completed scan's summary line reports an actual error
+            // count ("with <N> errors"); while a scrub/resilver is still in
+            // progress, `zpool status` has no running error tally, so this
+            // stays 0 until completion. `scrub_bytes_repaired` below is what
+            // moves during an in-progress scan.
+            register_intgauge(&pool_reg, "last_scrub_errors", "Errors found by the most recently completed scrub or resilver. Always 0 while one is still in progress, since zpool status doesn't report a running error count", scan.errors_repaired.unwrap_or(0) as i64).map_err(|e| e.to_string())?;
+
+            if let Some(ratio) = scan.progress_ratio {
+                register_gauge(&pool_reg, "scrub_progress_ratio", "Fraction of the current scrub or resilver scan completed, from 0.0 to 1.0", ratio).map_err(|e| e.to_string())?;
+            }
+
+            if let (Some(scanned), Some(total)) = (scan.bytes_scanned, scan.bytes_total) {
+                register_intgauge(&pool_reg, "scrub_bytes_scanned", "Bytes scanned so far by the in-progress scrub/resilver", scanned as i64).map_err(|e| e.to_string())?;
+                register_intgauge(&pool_reg, "scrub_bytes_total", "Total bytes to scan for the in-progress scrub/resilver", total as i64).map_err(|e| e.to_string())?;
+            }
+
+            if let Some(repaired) = scan.bytes_repaired {
+                register_intgauge(&pool_reg, "scrub_bytes_repaired", "Bytes repaired so far by the in-progress scrub/resilver", repaired as i64).map_err(|e| e.to_string())?;
+            }
+
+            if let Some(seconds) = scan.time_to_go_seconds {
+                register_intgauge(&pool_reg, "scrub_time_remaining_seconds", "Estimated time remaining for the in-progress scrub/resilver", seconds as i64).map_err(|e| e.to_string())?;
+            }
+
+            if let Some(vdev_tree) = &status.vdev_tree {
+                registries.extend(register_vdev_status(&labels, vdev_tree).map_err(|e| e.to_string())?);
             }
         }
 
@@ -221,7 +682,7 @@ async fn metrics_endpoint() -> impl Responder {
             } else if device.is_pool_or_vdev() {
                 // Register the metrics of the last vdev before overwriting it.
                 if let Some(vdev) = last_vdev_data {
-                    let reg = register_vdev_stats(vdev, device, device.name.clone(), labels.clone()).unwrap();
+                    let reg = register_vdev_stats(vdev, device, device.name.clone(), labels.clone()).map_err(|e| e.to_string())?;
 
                     registries.push(reg);
                 }
@@ -239,14 +700,14 @@ async fn metrics_endpoint() -> impl Responder {
                 }
 
                 // Create the device metric registry and collect io stats metrics
-                let device_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone())).unwrap();
-                device.io_stats.collect_metrics(&device_reg).unwrap();
+                let device_reg = Registry::new_custom(Some("zfs".to_string()), Some(labels.clone())).map_err(|e| e.to_string())?;
+                device.io_stats.collect_metrics(&device_reg).map_err(|e| e.to_string())?;
 
                 // Find the disk, and its vdev in the pool. After its found, register the disk's health and error stats.
                 for pool_vdev in pool.vdevs().iter() {
                     if let Some(pool_disk) = pool_vdev.disks().iter().find(|disk| String::from(disk.path().as_os_str().to_str().unwrap_or("")).contains(&device.name)) {
-                        registries.extend(register_health(labels, pool_disk.health().clone()).unwrap());
-                        register_error_stats(&device_reg, pool_disk.error_statistics().clone()).unwrap();
+                        registries.extend(register_health(labels, pool_disk.health().clone()).map_err(|e| e.to_string())?);
+                        register_error_stats(&device_reg, pool_disk.error_statistics().clone()).map_err(|e| e.to_string())?;
 
                         last_vdev_data = Some(pool_vdev);
                         break;
@@ -259,10 +720,37 @@ async fn metrics_endpoint() -> impl Responder {
 
         // Push the last vdev to the registry list
         if let (Some(device), Some(vdev)) = (last_vdev, last_vdev_data) {
-            registries.push(register_vdev_stats(vdev, device, device.name.clone(), labels.clone()).unwrap());
+            registries.push(register_vdev_stats(vdev, device, device.name.clone(), labels.clone()).map_err(|e| e.to_string())?);
         }
+
+        Ok(registries)
+        })();
+
+        // Isolate failures to the pool that produced them: log and skip
+        // rather than taking down the rest of the scrape, and surface the
+        // outcome as a metric so operators can alert on partial failures.
+        let success_reg = Registry::new_custom(
+            Some("zfs_exporter".to_string()),
+            Some(HashMap::from([(String::from("pool"), pool_name.clone())])),
+        ).unwrap();
+
+        match pool_result {
+            Ok(pool_registries) => {
+                register_intgauge(&success_reg, "scrape_success", "Whether the last scrape of this pool's metrics succeeded", 1).unwrap();
+                registries.extend(pool_registries);
+            }
+            Err(e) => {
+                error!("Failed to collect metrics for pool '{}', skipping it this scrape: {}", pool_name, e);
+                register_intgauge(&success_reg, "scrape_success", "Whether the last scrape of this pool's metrics succeeded", 0).unwrap();
+            }
+        }
+        registries.push(success_reg);
     }
 
+    let duration_reg = Registry::new_custom(Some("zfs_exporter".to_string()), None).unwrap();
+    register_gauge(&duration_reg, "scrape_duration_seconds", "How long the last scrape took, in seconds", scrape_start.elapsed().as_secs_f64()).unwrap();
+    registries.push(duration_reg);
+
     // Construct the response string from all registeries.
     let mut resp = String::new();
     for reg in registries.iter() {
@@ -299,19 +787,76 @@ impl IoStats {
 
     fn collect_metrics(&self, reg: &Registry) -> prometheus::Result<()> {
         if let (Some(capacity), Some(available)) = (self.capacity, self.available) {
-            register_intcounter(&reg, "capacity", "The capacity of the device in bytes", capacity)?;
-            register_intcounter(&reg, "available", "The available bytes in the device", available)?;
+            register_intgauge(&reg, "capacity", "The capacity of the device in bytes", capacity as i64)?;
+            register_intgauge(&reg, "available", "The available bytes in the device", available as i64)?;
         }
 
-        register_intcounter(&reg, "read_operations", "The read operations for this device per second", self.read_op)?;
-        register_intcounter(&reg, "write_operations", "The write operations for this device per second", self.write_op)?;
-        register_intcounter(&reg, "read_bandwidth", "The read bandwidth for this device in bytes per second", self.read_band)?;
-        register_intcounter(&reg, "write_bandwidth", "The write bandwidth for this device in bytes per second", self.write_band)?;
+        register_intgauge(&reg, "read_operations", "The read operations for this device per second", self.read_op as i64)?;
+        register_intgauge(&reg, "write_operations", "The write operations for this device per second", self.write_op as i64)?;
+        register_intgauge(&reg, "read_bandwidth", "The read bandwidth for this device in bytes per second", self.read_band as i64)?;
+        register_intgauge(&reg, "write_bandwidth", "The write bandwidth for this device in bytes per second", self.write_band as i64)?;
 
         Ok(())
     }
 }
 
+/// The ZFS health states `zpool list`'s HEALTH column can print.
+const ZPOOL_HEALTH_STATES: [&str; 7] = ["ONLINE", "DEGRADED", "FAULTED", "OFFLINE", "UNAVAIL", "REMOVED", "SUSPENDED"];
+
+/// Parses a numeric `zpool list -Hp` column, treating the `-` ZFS prints
+/// for an unavailable field as absent rather than a parse failure.
+fn parse_optional_num(s: &str) -> Option<f64> {
+    if s == "-" {
+        None
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// The numeric fields of one `zpool list -Hp <pool>` row. Every field is
+/// optional: ZFS prints `-` for
+/// stats a pool doesn't track (e.g. fragmentation), and the exact column
+/// count/order around FRAG/CAP/DEDUP varies across ZFS versions.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct PoolListStats {
+    size: Option<u64>,
+    alloc: Option<u64>,
+    free: Option<u64>,
+    fragmentation_percent: Option<f64>,
+    capacity_percent: Option<f64>,
+    dedup_ratio: Option<f64>,
+}
+
+impl PoolListStats {
+    /// Parses a single tab-separated `zpool list -Hp` row. `NAME`, `SIZE`,
+    /// `ALLOC`, and `FREE` are always the leading columns, but `FRAG`,
+    /// `CAP`, and `DEDUP` are instead located relative to `HEALTH`, since
+    /// the optional `CKPOINT`/`EXPANDSZ`/`ALTROOT` columns shift their
+    /// position across ZFS versions.
+    fn parse(row: &str) -> PoolListStats {
+        let cols: Vec<&str> = row.split('\t').collect();
+        let mut stats = PoolListStats::default();
+
+        if cols.len() < 4 {
+            return stats;
+        }
+
+        stats.size = cols.get(1).and_then(|s| parse_optional_num(s)).map(|n| n as u64);
+        stats.alloc = cols.get(2).and_then(|s| parse_optional_num(s)).map(|n| n as u64);
+        stats.free = cols.get(3).and_then(|s| parse_optional_num(s)).map(|n| n as u64);
+
+        if let Some(health_idx) = cols.iter().position(|c| ZPOOL_HEALTH_STATES.contains(c)) {
+            if health_idx >= 3 {
+                stats.dedup_ratio = cols.get(health_idx - 1).and_then(|s| parse_optional_num(s.trim_end_matches('x')));
+                stats.capacity_percent = cols.get(health_idx - 2).and_then(|s| parse_optional_num(s.trim_end_matches('%')));
+                stats.fragmentation_percent = cols.get(health_idx - 3).and_then(|s| parse_optional_num(s.trim_end_matches('%')));
+            }
+        }
+
+        stats
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Device {
     name: String,
@@ -369,6 +914,82 @@ impl Device {
     }
 }
 
+/// Where pool-level IO statistics are read from.
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+enum IostatSource {
+    /// Read the kernel's ZFS kstats directly (`/proc/spl/kstat/zfs/<pool>/io`).
+    /// No subprocess and no sampling sleep, but the counters are cumulative
+    /// totals rather than the per-second rates `zpool iostat` reports.
+    Kstat,
+    /// Shell out to `zpool iostat -Hpvy <pool> 1 1`. Blocks for the full 1
+    /// second sampling window per pool.
+    Command,
+}
+
+/// IO counters read from the kernel's ZFS kstat interface for a pool.
+///
+/// Unlike the sampled numbers `zpool iostat` reports, these are raw
+/// cumulative counters, so they map directly onto Prometheus counter
+/// semantics without needing a sampling window.
+#[derive(Debug, PartialEq, Eq)]
+struct KstatIo {
+    nread: u64,
+    nwritten: u64,
+    reads: u64,
+    writes: u64,
+}
+
+impl KstatIo {
+    /// Parses the contents of a `/proc/spl/kstat/zfs/<pool>/io` file.
+    ///
+    /// The file has a fixed layout: a header line, a line naming the
+    /// columns, then a single line of whitespace-separated integer values in
+    /// that same column order.
+    fn parse(contents: &str) -> Option<KstatIo> {
+        let mut lines = contents.lines();
+        let _header = lines.next()?;
+        let columns: Vec<&str> = lines.next()?.split_whitespace().collect();
+        let values: Vec<&str> = lines.next()?.split_whitespace().collect();
+
+        if columns.len() != values.len() {
+            return None;
+        }
+
+        let field = |name: &str| -> Option<u64> {
+            columns.iter().position(|&c| c == name)
+                .and_then(|i| values.get(i))
+                .and_then(|v| v.parse().ok())
+        };
+
+        Some(KstatIo {
+            nread: field("nread")?,
+            nwritten: field("nwritten")?,
+            reads: field("reads")?,
+            writes: field("writes")?,
+        })
+    }
+
+    /// Reads and parses the kstat io file for `pool_name`.
+    fn read_for_pool(pool_name: &str) -> std::io::Result<KstatIo> {
+        let path = format!("/proc/spl/kstat/zfs/{}/io", pool_name);
+        let contents = std::fs::read_to_string(path)?;
+
+        KstatIo::parse(&contents).ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unexpected kstat io format for pool '{}'", pool_name),
+        ))
+    }
+
+    fn collect_metrics(&self, reg: &Registry) -> prometheus::Result<()> {
+        register_intcounter(reg, "read_bytes_total", "Cumulative bytes read from this pool, from kstat", self.nread)?;
+        register_intcounter(reg, "write_bytes_total", "Cumulative bytes written to this pool, from kstat", self.nwritten)?;
+        register_intcounter(reg, "read_ops_total", "Cumulative read operations on this pool, from kstat", self.reads)?;
+        register_intcounter(reg, "write_ops_total", "Cumulative write operations on this pool, from kstat", self.writes)?;
+
+        Ok(())
+    }
+}
+
 /// ZFS metrics exporter for Prometheus!
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -384,6 +1005,10 @@ struct Args {
    /// The lowest log level (off, error, warn, info, debug, or trace).
    #[arg(long, default_value_t = String::from("info"))]
    log_level: String,
+
+   /// Where to source pool-level IO statistics from.
+   #[arg(long, value_enum, default_value = "command")]
+   iostat_source: IostatSource,
 }
 
 #[actix_web::main]
@@ -418,12 +1043,209 @@ async fn main() -> std::io::Result<()> {
         //.chain(fern::log_file("output.log")?)
         .apply().expect("Failure to initialize fern logger!");
 
-    HttpServer::new(|| {
+    let iostat_source = web::Data::new(args.iostat_source.clone());
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(iostat_source.clone())
             .wrap(Logger::default())
             .service(metrics_endpoint)
     })
     .bind((args.bind_address, args.port))?
     .run()
     .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kstat_io_parse_reads_named_columns_regardless_of_order() {
+        let contents = "\
+23 1 0x01 7 1960 1234567890 9876543210
+name                            type data
+nread                           4    1024
+nwritten                        4    2048
+reads                           4    10
+writes                          4    20
+wtime                           4    0
+";
+
+        let kstat = KstatIo::parse(contents).expect("valid kstat io file should parse");
+        assert_eq!(kstat, KstatIo { nread: 1024, nwritten: 2048, reads: 10, writes: 20 });
+    }
+
+    #[test]
+    fn kstat_io_parse_rejects_mismatched_column_value_counts() {
+        let contents = "\
+23 1 0x01 7 1960 1234567890 9876543210
+nread    nwritten reads    writes
+1024     2048     10
+";
+
+        assert_eq!(KstatIo::parse(contents), None);
+    }
+
+    #[test]
+    fn kstat_io_parse_rejects_missing_required_field() {
+        let contents = "\
+23 1 0x01 7 1960 1234567890 9876543210
+nread                           type
+1024                            4
+";
+
+        assert_eq!(KstatIo::parse(contents), None);
+    }
+
+    #[test]
+    fn kstat_io_parse_rejects_truncated_file() {
+        assert_eq!(KstatIo::parse("just a header\n"), None);
+        assert_eq!(KstatIo::parse(""), None);
+    }
+
+    fn vdev(name: &str, level: usize, children: Vec<VdevStatus>) -> VdevStatus {
+        VdevStatus {
+            name: name.to_string(),
+            level,
+            state: Some(String::from("ONLINE")),
+            read_errors: 0,
+            write_errors: 0,
+            checksum_errors: 0,
+            children,
+        }
+    }
+
+    #[test]
+    fn vdev_status_parse_tree_handles_logs_cache_and_spares_sections() {
+        let lines = [
+            "tank        ONLINE       0     0     0",
+            "  mirror-0  ONLINE       0     0     0",
+            "    sda     ONLINE       0     0     0",
+            "    sdb     ONLINE       0     0     0",
+            "logs",
+            "  sdc       ONLINE       0     0     0",
+            "cache",
+            "  sdd       ONLINE       0     0     0",
+            "spares",
+            "  sde       AVAIL        0     0     0",
+        ];
+
+        let tree = VdevStatus::parse_tree(&lines).expect("a tree with a root should parse");
+
+        let expected = vdev("tank", 0, vec![
+            vdev("mirror-0", 1, vec![vdev("sda", 2, vec![]), vdev("sdb", 2, vec![])]),
+            VdevStatus { name: String::from("logs"), level: 1, state: None, read_errors: 0, write_errors: 0, checksum_errors: 0, children: vec![vdev("sdc", 2, vec![])] },
+            VdevStatus { name: String::from("cache"), level: 1, state: None, read_errors: 0, write_errors: 0, checksum_errors: 0, children: vec![vdev("sdd", 2, vec![])] },
+            VdevStatus { name: String::from("spares"), level: 1, state: None, read_errors: 0, write_errors: 0, checksum_errors: 0, children: vec![VdevStatus { name: String::from("sde"), level: 2, state: Some(String::from("AVAIL")), read_errors: 0, write_errors: 0, checksum_errors: 0, children: vec![] }] },
+        ]);
+
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn vdev_status_parse_tree_handles_a_simple_pool_with_no_sections() {
+        let lines = [
+            "NAME        STATE     READ WRITE CKSUM",
+            "tank        ONLINE       0     0     0",
+            "  sda       ONLINE       0     0     0",
+        ];
+
+        let tree = VdevStatus::parse_tree(&lines).expect("a tree with a root should parse");
+        assert_eq!(tree, vdev("tank", 0, vec![vdev("sda", 1, vec![])]));
+    }
+
+    #[test]
+    fn vdev_status_parse_tree_returns_none_for_empty_body() {
+        assert_eq!(VdevStatus::parse_tree(&[]), None);
+        assert_eq!(VdevStatus::parse_tree(&["NAME STATE READ WRITE CKSUM", ""]), None);
+    }
+
+    #[test]
+    fn parse_scan_status_reads_completed_scrub_summary() {
+        let status = parse_scan_status("scrub repaired 0B in 0 days 03:02:00 with 0 errors on Sun Jan  1 00:00:00 2024");
+
+        assert!(!status.scrub_in_progress);
+        assert_eq!(status.errors_repaired, Some(0));
+        // The completed-scan summary reads "scrub repaired 0B", with the
+        // amount *after* "repaired", so this token-before lookup doesn't
+        // apply to it; it's only meaningful for the in-progress phrasing
+        // below ("<size> repaired, ...").
+        assert_eq!(status.bytes_repaired, None);
+    }
+
+    #[test]
+    fn parse_scan_status_reads_in_progress_resilver() {
+        let status = parse_scan_status(
+            "resilver in progress since Tue Jan  1 00:00:00 2024 1.2T scanned at 100M/s, 600G issued at 50M/s, 2T total 5G repaired, 30.00% done, 0 days 02:00:00 to go",
+        );
+
+        assert!(status.resilver_in_progress);
+        assert_eq!(status.progress_ratio, Some(30.0 / 100.0));
+        assert_eq!(status.bytes_total, Some(parse_zfs_size("2T").unwrap()));
+        assert_eq!(status.bytes_repaired, Some(parse_zfs_size("5G").unwrap()));
+        // No completed-scan summary line means no error count is available yet.
+        assert_eq!(status.errors_repaired, None);
+        assert_eq!(status.time_to_go_seconds, Some(2 * 3600));
+    }
+
+    #[test]
+    fn parse_zfs_size_handles_all_units() {
+        assert_eq!(parse_zfs_size("0B"), Some(0));
+        assert_eq!(parse_zfs_size("512K"), Some(512 * 1024));
+        assert_eq!(parse_zfs_size("2T"), Some(2 * 1024_u64.pow(4)));
+        assert_eq!(parse_zfs_size("garbage"), None);
+    }
+
+    #[test]
+    fn parse_time_to_go_reads_days_hms_suffix() {
+        assert_eq!(parse_time_to_go("30.00% done, 0 days 02:00:00 to go"), Some(2 * 3600));
+        assert_eq!(parse_time_to_go("30.00% done, 1 day 00:10:05 to go"), Some(86400 + 605));
+        assert_eq!(parse_time_to_go("no eta here"), None);
+    }
+
+    #[test]
+    fn pool_list_stats_parse_reads_frag_cap_dedup_relative_to_health() {
+        let row = "tank\t1099511627776\t549755813888\t549755813888\t-\t-\t15%\t50%\t1.00x\tONLINE\t-";
+
+        let stats = PoolListStats::parse(row);
+
+        assert_eq!(stats.size, Some(1099511627776));
+        assert_eq!(stats.alloc, Some(549755813888));
+        assert_eq!(stats.free, Some(549755813888));
+        assert_eq!(stats.fragmentation_percent, Some(15.0));
+        assert_eq!(stats.capacity_percent, Some(50.0));
+        assert_eq!(stats.dedup_ratio, Some(1.0));
+    }
+
+    #[test]
+    fn pool_list_stats_parse_still_locates_columns_when_ckpoint_expandsz_are_absent() {
+        // Older ZFS versions omit the CKPOINT/EXPANDSZ columns entirely, shifting
+        // everything after FREE left by two; FRAG/CAP/DEDUP must still be found
+        // relative to HEALTH rather than by a fixed column index.
+        let row = "tank\t1099511627776\t549755813888\t549755813888\t15%\t50%\t1.00x\tONLINE\t-";
+
+        let stats = PoolListStats::parse(row);
+
+        assert_eq!(stats.fragmentation_percent, Some(15.0));
+        assert_eq!(stats.capacity_percent, Some(50.0));
+        assert_eq!(stats.dedup_ratio, Some(1.0));
+    }
+
+    #[test]
+    fn pool_list_stats_parse_treats_dash_as_unavailable() {
+        let row = "tank\t1099511627776\t549755813888\t549755813888\t-\t-\t-\t-\t-\tONLINE\t-";
+
+        let stats = PoolListStats::parse(row);
+
+        assert_eq!(stats.size, Some(1099511627776));
+        assert_eq!(stats.fragmentation_percent, None);
+        assert_eq!(stats.capacity_percent, None);
+        assert_eq!(stats.dedup_ratio, None);
+    }
+
+    #[test]
+    fn pool_list_stats_parse_defaults_on_too_few_columns() {
+        assert_eq!(PoolListStats::parse("tank\t-\t-"), PoolListStats::default());
+    }
 }
\ No newline at end of file